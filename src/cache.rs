@@ -0,0 +1,144 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+use crate::{format::Format, resize::ResizeOp};
+
+/// Subdirectory (relative to the output folder) that hashed outputs live in.
+pub const PROCESSED_DIR: &str = "processed_images";
+
+/// Options that affect the processed output and are therefore part of the
+/// cache key, so a changed resize op, format or metadata setting always gets a
+/// fresh output filename.
+#[derive(Hash)]
+pub struct ProcessOptions {
+    pub resize_op: Option<ResizeOp>,
+    pub format: Format,
+    pub strip_metadata: bool,
+}
+
+/// Computes the cache-keyed output path for a source file's `bytes` and
+/// `opts` inside `processed_dir`, as `<16-hex digest><2-hex counter>.<ext>`.
+///
+/// A sidecar `.key` file next to each output stores a second, independently
+/// seeded hash of the same input, so a same-digest entry from different
+/// content (a true collision) is detected and given the next counter instead
+/// of being mistaken for a cache hit.
+///
+/// Returns `(path, true)` for a verified cache hit (skip reprocessing), or
+/// `(path, false)` for the first free counter slot otherwise. If all 256
+/// counter slots are taken, the occupied `ff` slot is returned with `false`,
+/// so its output gets overwritten.
+pub fn hashed_output_path(
+    processed_dir: &Path,
+    bytes: &[u8],
+    opts: &ProcessOptions,
+    ext: &str,
+) -> (PathBuf, bool) {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    opts.hash(&mut hasher);
+    let digest = hasher.finish();
+
+    let mut verify_hasher = DefaultHasher::new();
+    "tinify-pics-cache-verify".hash(&mut verify_hasher);
+    bytes.hash(&mut verify_hasher);
+    opts.hash(&mut verify_hasher);
+    let verify = format!("{:016x}", verify_hasher.finish());
+
+    for counter in 0u8..=0xff {
+        let candidate = processed_dir.join(format!("{:016x}{:02x}.{}", digest, counter, ext));
+        let sidecar = processed_dir.join(format!("{:016x}{:02x}.{}.key", digest, counter, ext));
+
+        if !candidate.exists() {
+            let _ = fs::write(&sidecar, &verify);
+            return (candidate, false);
+        }
+        if fs::read_to_string(&sidecar).map(|stored| stored == verify).unwrap_or(false) {
+            return (candidate, true);
+        }
+        // Digest collision with different content (or a pre-existing output with
+        // no sidecar): move on to the next counter instead of overwriting it.
+    }
+
+    // Exhausted the 2-hex counter space; extremely unlikely in practice.
+    let fallback = processed_dir.join(format!("{:016x}ff.{}", digest, ext));
+    (fallback, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("tinify-pics-cache-test-{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn opts() -> ProcessOptions {
+        ProcessOptions { resize_op: Some(ResizeOp::Pad(200)), format: Format::Png, strip_metadata: false }
+    }
+
+    #[test]
+    fn fresh_content_is_not_a_cache_hit() {
+        let dir = scratch_dir("fresh");
+        let (path, hit) = hashed_output_path(&dir, b"hello", &opts(), "png");
+        assert!(!hit);
+        assert!(path.to_str().unwrap().ends_with(".png"));
+    }
+
+    #[test]
+    fn identical_content_and_opts_is_a_cache_hit() {
+        let dir = scratch_dir("hit");
+        let (first_path, _) = hashed_output_path(&dir, b"hello", &opts(), "png");
+        // Simulate the output actually having been written, as the caller would.
+        fs::write(&first_path, b"fake output").unwrap();
+
+        let (second_path, hit) = hashed_output_path(&dir, b"hello", &opts(), "png");
+        assert!(hit);
+        assert_eq!(first_path, second_path);
+    }
+
+    #[test]
+    fn different_content_gets_a_different_path() {
+        let dir = scratch_dir("diff-content");
+        let (path_a, _) = hashed_output_path(&dir, b"hello", &opts(), "png");
+        let (path_b, _) = hashed_output_path(&dir, b"goodbye", &opts(), "png");
+        assert_ne!(path_a, path_b);
+    }
+
+    #[test]
+    fn different_opts_gets_a_different_path() {
+        let dir = scratch_dir("diff-opts");
+        let other_opts = ProcessOptions {
+            resize_op: Some(ResizeOp::Pad(640)),
+            format: Format::Png,
+            strip_metadata: false,
+        };
+        let (path_a, _) = hashed_output_path(&dir, b"hello", &opts(), "png");
+        let (path_b, _) = hashed_output_path(&dir, b"hello", &other_opts, "png");
+        assert_ne!(path_a, path_b);
+    }
+
+    #[test]
+    fn a_preexisting_file_with_no_sidecar_is_treated_as_a_collision() {
+        let dir = scratch_dir("collision");
+        // Write a file directly at the would-be candidate path, bypassing
+        // `hashed_output_path` so no sidecar `.key` file exists for it.
+        let (candidate, _) = hashed_output_path(&dir, b"hello", &opts(), "png");
+        fs::remove_file(
+            dir.join(format!("{}.key", candidate.file_name().unwrap().to_str().unwrap())),
+        )
+        .unwrap();
+        fs::write(&candidate, b"someone else's file").unwrap();
+
+        let (next_path, hit) = hashed_output_path(&dir, b"hello", &opts(), "png");
+        assert!(!hit);
+        assert_ne!(next_path, candidate);
+    }
+}
@@ -0,0 +1,43 @@
+use std::{fs, path::Path};
+
+use log::warn;
+use serde::Deserialize;
+
+use crate::{format::FormatArg, resize::ResizeOp, size::SizeOption};
+
+/// Defaults loaded from a `tinify.toml` in the input folder. CLI flags win
+/// when both are set.
+#[derive(Deserialize, Default, Debug)]
+pub struct Config {
+    pub output_folder: Option<String>,
+    pub resize: Option<String>,
+    pub format: Option<String>,
+    pub size: Option<String>,
+}
+
+impl Config {
+    /// Loads `tinify.toml` from `input_folder`, if present; otherwise returns
+    /// an all-`None` config.
+    pub fn load(input_folder: &str) -> Config {
+        let path = Path::new(input_folder).join("tinify.toml");
+        match fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents).unwrap_or_else(|err| {
+                warn!("Could not parse '{}': {}", path.display(), err);
+                Config::default()
+            }),
+            Err(_) => Config::default(),
+        }
+    }
+
+    pub fn resize_op(&self) -> Option<ResizeOp> {
+        self.resize.as_deref().and_then(|s| ResizeOp::parse(s).ok())
+    }
+
+    pub fn format_arg(&self) -> Option<FormatArg> {
+        self.format.as_deref().and_then(|s| FormatArg::parse(s).ok())
+    }
+
+    pub fn size_option(&self) -> Option<SizeOption> {
+        self.size.as_deref().and_then(|s| SizeOption::parse(s).ok())
+    }
+}
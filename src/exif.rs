@@ -0,0 +1,82 @@
+use std::{
+    fs::File,
+    io::{BufReader, Cursor},
+};
+
+use exif::{experimental::Writer, Context, Exif};
+use image::{
+    imageops::{flip_horizontal, flip_vertical, rotate180, rotate270, rotate90},
+    DynamicImage,
+};
+
+/// Reads the EXIF block (if any) from `file`.
+pub fn read(file: &str) -> Option<Exif> {
+    let file = File::open(file).ok()?;
+    let mut reader = BufReader::new(file);
+    exif::Reader::new().read_from_container(&mut reader).ok()
+}
+
+/// Reads the EXIF orientation tag (if any) from an already-read EXIF block.
+pub fn orientation(exif: &Exif) -> Option<u32> {
+    let field = exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY)?;
+    field.value.get_uint(0)
+}
+
+/// Applies the rotation/flip an EXIF `orientation` tag describes, so the
+/// image displays upright instead of however the camera originally wrote it.
+///
+/// Orientations 5 and 7 (transpose/transverse) are rare in practice and are
+/// left as a no-op rather than chaining a flip with a rotate for them.
+pub fn apply_orientation(img: &DynamicImage, orientation: u32) -> DynamicImage {
+    match orientation {
+        2 => DynamicImage::ImageRgba8(flip_horizontal(img)),
+        3 => DynamicImage::ImageRgba8(rotate180(img)),
+        4 => DynamicImage::ImageRgba8(flip_vertical(img)),
+        6 => DynamicImage::ImageRgba8(rotate90(img)),
+        8 => DynamicImage::ImageRgba8(rotate270(img)),
+        _ => img.clone(),
+    }
+}
+
+/// Splices a sanitized copy of `exif` (GPS fields and the orientation tag
+/// dropped, since the pixels have already been turned upright) into
+/// `jpeg_bytes` as an APP1 segment, right after the SOI marker. `jpeg_bytes`
+/// must be a freshly-encoded JPEG with no APP1 segment of its own yet.
+///
+/// No-op if `jpeg_bytes` doesn't look like a JPEG, or if the sanitized EXIF
+/// block is too big to fit in a single APP1 segment (max ~64KB).
+pub fn embed_sanitized(jpeg_bytes: &mut Vec<u8>, exif: &Exif) {
+    if jpeg_bytes.len() < 2 || jpeg_bytes[0..2] != [0xFF, 0xD8] {
+        return;
+    }
+
+    let fields: Vec<_> = exif
+        .fields()
+        .filter(|f| f.tag.context() != Context::Gps && f.tag != exif::Tag::Orientation)
+        .collect();
+    if fields.is_empty() {
+        return;
+    }
+
+    let mut writer = Writer::new();
+    for field in &fields {
+        writer.push_field(field);
+    }
+    let mut tiff = Cursor::new(Vec::new());
+    if writer.write(&mut tiff, exif.little_endian()).is_err() {
+        return;
+    }
+    let tiff = tiff.into_inner();
+
+    let segment_len = 2 + 6 + tiff.len();
+    if segment_len > 0xFFFF {
+        return;
+    }
+
+    let mut app1 = vec![0xFF, 0xE1];
+    app1.extend_from_slice(&(segment_len as u16).to_be_bytes());
+    app1.extend_from_slice(b"Exif\0\0");
+    app1.extend_from_slice(&tiff);
+
+    jpeg_bytes.splice(2..2, app1);
+}
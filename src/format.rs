@@ -0,0 +1,137 @@
+use std::io::Cursor;
+
+use image::{DynamicImage, ImageOutputFormat, ImageResult};
+
+/// Output encoding for the processed image buffer before it is handed to Tinify.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Format {
+    Png,
+    Jpeg(u8),
+    WebP,
+}
+
+impl Format {
+    /// File extension matching this format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Png => "png",
+            Format::Jpeg(_) => "jpg",
+            Format::WebP => "webp",
+        }
+    }
+
+    /// Encodes `img` into `bytes` using this format.
+    pub fn encode(self, img: &DynamicImage, bytes: &mut Vec<u8>) -> ImageResult<()> {
+        match self {
+            Format::Png => img.write_to(&mut Cursor::new(bytes), ImageOutputFormat::Png),
+            Format::Jpeg(quality) => {
+                img.write_to(&mut Cursor::new(bytes), ImageOutputFormat::Jpeg(quality))
+            }
+            Format::WebP => {
+                let encoder = webp::Encoder::from_image(img)
+                    .map_err(|e| std::io::Error::other(format!("Could not create WebP encoder: {}", e)))?;
+                bytes.extend_from_slice(&encoder.encode(80.0));
+                Ok(())
+            }
+        }
+    }
+
+    /// The `auto` rule: lossy sources (jpg/jpeg) stay JPEG, everything else becomes PNG.
+    fn detect(file: &str) -> Format {
+        let lower = file.to_lowercase();
+        if lower.ends_with(".jpg") || lower.ends_with(".jpeg") {
+            Format::Jpeg(85)
+        } else {
+            Format::Png
+        }
+    }
+}
+
+/// Parsed `--format` value; `auto` is resolved against a concrete source file
+/// via `FormatArg::resolve`, since the right format depends on the input.
+#[derive(Clone, Copy, Debug)]
+pub enum FormatArg {
+    Auto,
+    Explicit(Format),
+}
+
+impl FormatArg {
+    /// Parses `auto`, `png`, `jpeg[:quality]` or `webp`.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "auto" => Ok(FormatArg::Auto),
+            "png" => Ok(FormatArg::Explicit(Format::Png)),
+            "webp" => Ok(FormatArg::Explicit(Format::WebP)),
+            _ if s == "jpeg" || s.starts_with("jpeg:") => {
+                let quality = match s.strip_prefix("jpeg:") {
+                    Some(q) => q
+                        .parse::<u8>()
+                        .map_err(|_| format!("invalid jpeg quality '{}'", q))?,
+                    None => 85,
+                };
+                Ok(FormatArg::Explicit(Format::Jpeg(quality)))
+            }
+            other => Err(format!(
+                "unknown format '{}', expected auto, png, jpeg[:quality] or webp",
+                other
+            )),
+        }
+    }
+
+    /// Resolves `Auto` against `file`'s extension; passes explicit formats through.
+    pub fn resolve(self, file: &str) -> Format {
+        match self {
+            FormatArg::Auto => Format::detect(file),
+            FormatArg::Explicit(format) => format,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extensions() {
+        assert_eq!(Format::Png.extension(), "png");
+        assert_eq!(Format::Jpeg(85).extension(), "jpg");
+        assert_eq!(Format::WebP.extension(), "webp");
+    }
+
+    #[test]
+    fn auto_keeps_jpg_and_jpeg_as_jpeg() {
+        assert_eq!(FormatArg::Auto.resolve("photo.jpg"), Format::Jpeg(85));
+        assert_eq!(FormatArg::Auto.resolve("photo.JPEG"), Format::Jpeg(85));
+    }
+
+    #[test]
+    fn auto_falls_back_to_png() {
+        assert_eq!(FormatArg::Auto.resolve("photo.png"), Format::Png);
+        assert_eq!(FormatArg::Auto.resolve("photo.webp"), Format::Png);
+        assert_eq!(FormatArg::Auto.resolve("photo"), Format::Png);
+    }
+
+    #[test]
+    fn parses_auto_png_webp() {
+        assert!(matches!(FormatArg::parse("auto"), Ok(FormatArg::Auto)));
+        assert!(matches!(FormatArg::parse("png"), Ok(FormatArg::Explicit(Format::Png))));
+        assert!(matches!(FormatArg::parse("webp"), Ok(FormatArg::Explicit(Format::WebP))));
+    }
+
+    #[test]
+    fn parses_jpeg_with_and_without_quality() {
+        assert!(matches!(FormatArg::parse("jpeg"), Ok(FormatArg::Explicit(Format::Jpeg(85)))));
+        assert!(matches!(FormatArg::parse("jpeg:90"), Ok(FormatArg::Explicit(Format::Jpeg(90)))));
+    }
+
+    #[test]
+    fn rejects_invalid_quality_and_unknown_format() {
+        assert!(FormatArg::parse("jpeg:not-a-number").is_err());
+        assert!(FormatArg::parse("bmp").is_err());
+    }
+
+    #[test]
+    fn explicit_resolve_ignores_the_file_name() {
+        assert_eq!(FormatArg::Explicit(Format::WebP).resolve("photo.jpg"), Format::WebP);
+    }
+}
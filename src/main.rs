@@ -1,20 +1,43 @@
-use std::{fs, io, path::Path};
-
-use clap::{AppSettings, Clap};
-use image::{
-    imageops::{overlay, resize},
-    DynamicImage, ImageResult,
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
 };
+
+use clap::{AppSettings, Parser};
+use image::ImageResult;
 use log::*;
-use tinify_rs::{tinify, tinify::Source};
+use rayon::prelude::*;
+use tinify_rs::tinify;
+
+mod cache;
+mod config;
+mod exif;
+mod format;
+mod resize;
+mod size;
+mod stats;
+use cache::ProcessOptions;
+use config::Config;
+use format::{Format, FormatArg};
+use resize::ResizeOp;
+use size::SizeOption;
+use stats::Savings;
 
-#[derive(Clap, Debug)]
+#[derive(Parser, Debug)]
 #[clap(version = "0.1", author = "Chris <tinify@caemor.de")]
 #[clap(setting = AppSettings::ColoredHelp)]
-/// Tinifys (png, jpg) and convert (png) images with the tinify api.
-/// It has two usage options: Single Image (`name`) and full Folder where everything inside a folder gets
-/// tinified (and possibly converted)
-struct Opts {
+/// Tinifys (png, jpg) and converts images with the tinify api, or reports stats about a folder
+enum Command {
+    /// Tinifys (png, jpg) and converts images with the tinify api.
+    /// It has two usage options: Single Image (`name`) and full Folder where everything inside a folder gets
+    /// tinified (and possibly converted)
+    Tinify(TinifyOpts),
+    /// Walks a folder and reports per-format counts, total bytes, and mean image size
+    Stats(StatsOpts),
+}
+
+#[derive(Parser, Debug)]
+struct TinifyOpts {
     /// Optional Tinify Key (alternative to .env file)
     #[clap(short, long)]
     key: Option<String>,
@@ -27,20 +50,53 @@ struct Opts {
     /// Folder for tinified images (only for folder operations)
     #[clap(short, long)]
     output_folder: Option<String>,
-    /// Appended Name pattern for tinified pictures
-    #[clap(short, long, default_value = "_tiny")]
-    pattern: String,
-    /// Size for Resizing
-    #[clap(short, long, default_value = "200")]
-    size: u32,
-    /// Option to only tinify picture for pngs (by default pngs get converted to size*size)
+    /// Size for Resizing (used for the default square-pad resize): `small` (200),
+    /// `medium` (640), `large` (1280), or a raw pixel count. Falls back to `tinify.toml`,
+    /// then 200.
+    #[clap(short, long, parse(try_from_str = SizeOption::parse))]
+    size: Option<SizeOption>,
+    /// How to resize images, e.g. `scale:800x600`, `fit-width:800`, `fit-height:600`,
+    /// `fit:800x600` or `pad:200`. Falls back to `tinify.toml`, then `pad:<size>`
+    /// (today's square overlay).
+    #[clap(short, long, parse(try_from_str = ResizeOp::parse))]
+    resize: Option<ResizeOp>,
+    /// Option to only tinify pictures without resizing/reformatting them first
     #[clap(short, long)]
     tinify_only: bool,
+    /// Strip EXIF/GPS metadata from re-encoded (jpeg) output instead of carrying over a
+    /// sanitized (GPS-free) copy. Has no effect with `--tinify-only`, since nothing is
+    /// re-encoded in that case.
+    #[clap(long)]
+    strip_metadata: bool,
+    /// Max parallel Tinify API calls for folder operations (default: number of CPUs)
+    #[clap(short, long)]
+    jobs: Option<usize>,
+    /// Output format: `auto` (keeps jpg as jpeg, else png), `png`, `jpeg[:quality]` or `webp`.
+    /// Falls back to `tinify.toml`, then `auto`. Has no effect with `--tinify-only`, since
+    /// nothing is re-encoded in that case.
+    #[clap(short, long, parse(try_from_str = FormatArg::parse))]
+    format: Option<FormatArg>,
+}
+
+#[derive(Parser, Debug)]
+struct StatsOpts {
+    /// Folder to report stats for
+    #[clap(short, long, default_value = "tinify")]
+    input_folder: String,
+    /// Recurse into subfolders
+    #[clap(short, long)]
+    recursive: bool,
 }
 
 fn main() -> ImageResult<()> {
     env_logger::init();
-    let opts: Opts = Opts::parse();
+    match Command::parse() {
+        Command::Tinify(opts) => run_tinify(opts),
+        Command::Stats(opts) => stats::run(&opts),
+    }
+}
+
+fn run_tinify(opts: TinifyOpts) -> ImageResult<()> {
     debug!("Opts: {:?}", opts);
 
     // Read tinify api key ...
@@ -53,123 +109,170 @@ fn main() -> ImageResult<()> {
     tinify::set_key(&key);
     debug!("Tinify Key set!");
 
+    // Load `tinify.toml` defaults from the input folder; explicit CLI flags override them
+    let config = Config::load(&opts.input_folder);
+    let output_folder = opts.output_folder.clone().or(config.output_folder.clone());
+    let format = opts.format.unwrap_or_else(|| config.format_arg().unwrap_or(FormatArg::Auto));
+    let size = opts
+        .size
+        .or_else(|| config.size_option())
+        .unwrap_or(SizeOption::Custom(200));
+    let resize_arg = opts.resize.or_else(|| config.resize_op());
+    let strip_metadata = opts.strip_metadata;
+
     // Check if resizing was desired
-    let size = if opts.tinify_only {
+    let resize_op = if opts.tinify_only {
         None
     } else {
-        Some(opts.size)
+        Some(resize_arg.unwrap_or(ResizeOp::Pad(size.pixels())))
     };
 
+    let savings = Savings::default();
+
     // Tinify single image
     if let Some(name) = opts.name {
-        tinify(name, None, &opts.pattern, size)?;
+        tinify(name, None, resize_op, format, strip_metadata, &savings)?;
     } else
     // Tinify all images in folder
     {
         if !Path::new(&opts.input_folder).exists() {
             fs::create_dir(&opts.input_folder)?;
         }
-        for entry in fs::read_dir(opts.input_folder)?
+        let entries: Vec<PathBuf> = fs::read_dir(&opts.input_folder)?
             .map(|res| res.map(|e| e.path()))
-            .collect::<Result<Vec<_>, io::Error>>()?
-        {
-            tinify(
-                entry
-                    .to_str()
-                    .expect("Unable to convert path to String")
-                    .to_owned(),
-                opts.output_folder.as_deref(),
-                &opts.pattern,
-                size,
-            )?;
-        }
+            .collect::<Result<Vec<_>, io::Error>>()?;
+
+        // Cap the thread pool size; 0 falls back to rayon's default
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(opts.jobs.unwrap_or(0))
+            .build()
+            .expect("Failed to build thread pool");
+
+        pool.install(|| {
+            entries.par_iter().try_for_each(|entry| {
+                tinify(
+                    entry
+                        .to_str()
+                        .expect("Unable to convert path to String")
+                        .to_owned(),
+                    output_folder.as_deref(),
+                    resize_op,
+                    format,
+                    strip_metadata,
+                    &savings,
+                )
+            })
+        })?;
     }
 
+    savings.print_summary();
+
     Ok(())
 }
 
-// Tinifies Images and possibly converts them if given some `convert_size` and a png image
+// Tinifies Images and possibly converts/reformats them if given a `resize_op`
 fn tinify(
     file: String,
     output: Option<&str>,
-    pattern: &str,
-    convert_size: Option<u32>,
+    resize_op: Option<ResizeOp>,
+    format: FormatArg,
+    strip_metadata: bool,
+    savings: &Savings,
 ) -> ImageResult<()> {
-    // Generate output path and exit if it already exists
-    let output = output
-        .map(|x| x.to_string())
-        .unwrap_or(file.clone())
-        .replace(".png", &format!("{}.png", pattern))
-        .replace(".jpg", &format!("{}.jpg", pattern));
-
-    let is_png = output.ends_with(".png");
-
-    // Filter alredy processed images
-    if output.contains(&format!("{}{}", &pattern, &pattern)) {
-        debug!("Image is an already tinified output! (file: '{}')", file);
+    let file_path = Path::new(&file);
+    let output_dir = output
+        .map(Path::new)
+        .or_else(|| file_path.parent())
+        .unwrap_or_else(|| Path::new("."));
+    let processed_dir = output_dir.join(cache::PROCESSED_DIR);
+
+    // Skip the folder our own output lives in (and anything inside it), so a
+    // re-run over the same input folder never tries to read it back in as a
+    // source image (processed_dir itself shows up as a `fs::read_dir` entry)
+    if file_path.is_dir() || file_path.starts_with(&processed_dir) {
+        debug!("Skipping processed-output path '{}'", file);
         return Ok(());
     }
 
-    // If output file already exists, stop processing and return Ok but output error message
-    if Path::new(&output).exists() {
+    let format = format.resolve(&file);
+
+    if !processed_dir.exists() {
+        fs::create_dir_all(&processed_dir)?;
+    }
+
+    let bytes = fs::read(&file)?;
+    let opts = ProcessOptions { resize_op, format, strip_metadata };
+    // Without a resize op nothing is re-encoded, so the output keeps the source's extension
+    let ext = match (resize_op, file_path.extension().and_then(|e| e.to_str())) {
+        (None, Some(source_ext)) => source_ext.to_lowercase(),
+        _ => format.extension().to_owned(),
+    };
+    let (output_path, cache_hit) = cache::hashed_output_path(&processed_dir, &bytes, &opts, &ext);
+
+    // Exact same content + options were already processed, skip the Tinify call entirely
+    if cache_hit {
         info!(
-            "Tinified Output for '{}' already exists! (output: '{}')",
-            file, output
+            "Cached output for '{}' already exists! (output: '{}')",
+            file,
+            output_path.display()
         );
         return Ok(());
     }
 
-    let source = match (is_png, convert_size) {
-        // Convert and tinify if png with size...
-        (true, Some(new_size)) => convert_and_tinify(&file, new_size)?,
+    let output_path_str = output_path.to_str().expect("Unable to convert path to String");
+    match resize_op {
+        // Convert and tinify if a resize op was requested...
+        Some(resize_op) => {
+            convert_and_tinify(&file, resize_op, format, strip_metadata, output_path_str)?
+        }
         // ... or else just tinify
-        (_, _) => tinify::from_file(&file),
+        None => tinify::from_file(&file).to_file(output_path_str)?,
     };
-    debug!("Image {} tinified", &file);
+    debug!("Tinified image {} written to file", output_path.display());
 
-    // Write to file
-    source.to_file(&output)?;
-    debug!("Tinified image {} written to file", &output);
+    // Track bytes saved for the end-of-run summary
+    let output_len = fs::metadata(&output_path)?.len();
+    savings.record(bytes.len() as u64, output_len);
 
     Ok(())
 }
 
-// Converts an image to square format and resizes it to `new_size` and directly tinifies it
+// Converts an image using the given `resize_op`/`format` and directly tinifies it.
+// The decode->re-encode below only carries pixels through, so a sanitized (GPS-free)
+// copy of the source EXIF block is spliced back in afterwards, unless `strip_metadata`
+// is set (jpeg output only; png/webp still come out with no EXIF at all).
 // Helper function for `tinify`
-fn convert_and_tinify(file: &str, new_size: u32) -> ImageResult<Source> {
+fn convert_and_tinify(
+    file: &str,
+    resize_op: ResizeOp,
+    format: Format,
+    strip_metadata: bool,
+    output_path: &str,
+) -> ImageResult<()> {
     // Open Image
-    let logo = image::open(&file)
-        .expect(&format!("Could not load image at {:?}", file))
-        .to_rgba8();
-
-    // Get proportions
-    let width = logo.width();
-    let height = logo.height();
-    let max_length = width.max(height);
-
-    // Create new background image
-    let mut img: DynamicImage = DynamicImage::new_rgba8(max_length, max_length);
-
-    // Overlay new background with logo
-    overlay(
-        &mut img,
-        &logo,
-        (max_length - width) / 2,
-        (max_length - height) / 2,
-    );
+    let img = image::open(file)?;
+
+    let source_exif = exif::read(file);
+
+    // Undo the camera's EXIF orientation so portrait shots don't come out rotated
+    let img = match source_exif.as_ref().and_then(exif::orientation) {
+        Some(orientation) => exif::apply_orientation(&img, orientation),
+        None => img,
+    };
 
     // Resize
-    let resized: DynamicImage = DynamicImage::ImageRgba8(resize(
-        &img,
-        new_size,
-        new_size,
-        image::imageops::FilterType::Lanczos3,
-    ));
+    let resized = resize_op.apply(&img);
 
     // Write to buffer/vec
     let mut bytes: Vec<u8> = Vec::new();
-    resized.write_to(&mut bytes, image::ImageOutputFormat::Png)?;
+    format.encode(&resized, &mut bytes)?;
+
+    if !strip_metadata {
+        if let (Format::Jpeg(_), Some(source_exif)) = (format, &source_exif) {
+            exif::embed_sanitized(&mut bytes, source_exif);
+        }
+    }
 
     // Tinify
-    Ok(tinify::from_buffer(&bytes))
+    Ok(tinify::from_buffer(&bytes).to_file(output_path)?)
 }
@@ -0,0 +1,190 @@
+use image::{
+    imageops::{overlay, resize, FilterType},
+    DynamicImage,
+};
+
+/// How an image should be resized before being tinified.
+///
+/// `Pad` reproduces the tool's original behavior (square overlay + downscale);
+/// the other variants let callers keep the original aspect ratio instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ResizeOp {
+    /// Resize to an exact `width`x`height`, ignoring the original aspect ratio.
+    Scale(u32, u32),
+    /// Resize to `width`, scaling the height to preserve aspect ratio.
+    FitWidth(u32),
+    /// Resize to `height`, scaling the width to preserve aspect ratio.
+    FitHeight(u32),
+    /// Resize to fit within a `width`x`height` box, aspect ratio preserved and
+    /// never upscaled past the original size.
+    Fit(u32, u32),
+    /// Pad the image into a square of `size`x`size` (today's default behavior).
+    Pad(u32),
+}
+
+impl ResizeOp {
+    /// Parses specs like `scale:800x600`, `fit-width:800`, `fit-height:600`,
+    /// `fit:800x600` or `pad:200`.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let (kind, rest) = s
+            .split_once(':')
+            .ok_or_else(|| format!("invalid resize spec '{}', expected '<kind>:<value>'", s))?;
+        match kind {
+            "scale" => {
+                let (w, h) = parse_dims(rest)?;
+                Ok(ResizeOp::Scale(w, h))
+            }
+            "fit-width" => Ok(ResizeOp::FitWidth(parse_dim(rest)?)),
+            "fit-height" => Ok(ResizeOp::FitHeight(parse_dim(rest)?)),
+            "fit" => {
+                let (w, h) = parse_dims(rest)?;
+                Ok(ResizeOp::Fit(w, h))
+            }
+            "pad" => Ok(ResizeOp::Pad(parse_dim(rest)?)),
+            other => Err(format!(
+                "unknown resize kind '{}', expected one of scale, fit-width, fit-height, fit, pad",
+                other
+            )),
+        }
+    }
+
+    /// Applies this resize operation to `img`, returning the resized image.
+    pub fn apply(self, img: &DynamicImage) -> DynamicImage {
+        let width = img.width();
+        let height = img.height();
+
+        match self {
+            ResizeOp::Scale(w, h) => DynamicImage::ImageRgba8(resize(img, w, h, FilterType::Lanczos3)),
+            ResizeOp::FitWidth(w) => {
+                let h = scaled_dim(w, width, height);
+                DynamicImage::ImageRgba8(resize(img, w, h, FilterType::Lanczos3))
+            }
+            ResizeOp::FitHeight(h) => {
+                let w = scaled_dim(h, height, width);
+                DynamicImage::ImageRgba8(resize(img, w, h, FilterType::Lanczos3))
+            }
+            ResizeOp::Fit(box_w, box_h) => {
+                let scale = (box_w as f64 / width as f64)
+                    .min(box_h as f64 / height as f64)
+                    .min(1.0);
+                let w = ((width as f64) * scale).round().max(1.0) as u32;
+                let h = ((height as f64) * scale).round().max(1.0) as u32;
+                DynamicImage::ImageRgba8(resize(img, w, h, FilterType::Lanczos3))
+            }
+            ResizeOp::Pad(size) => {
+                let max_length = width.max(height);
+                let mut padded = DynamicImage::new_rgba8(max_length, max_length);
+                overlay(
+                    &mut padded,
+                    &img.to_rgba8(),
+                    ((max_length - width) / 2).into(),
+                    ((max_length - height) / 2).into(),
+                );
+                DynamicImage::ImageRgba8(resize(&padded, size, size, FilterType::Lanczos3))
+            }
+        }
+    }
+}
+
+/// Computes the dual dimension for a fixed `fixed_dim`, preserving the
+/// `orig_fixed`/`orig_other` ratio.
+fn scaled_dim(fixed_dim: u32, orig_fixed: u32, orig_other: u32) -> u32 {
+    ((fixed_dim as u64 * orig_other as u64) / orig_fixed as u64).max(1) as u32
+}
+
+fn parse_dim(s: &str) -> Result<u32, String> {
+    s.parse::<u32>()
+        .map_err(|_| format!("invalid dimension '{}'", s))
+}
+
+fn parse_dims(s: &str) -> Result<(u32, u32), String> {
+    let (w, h) = s
+        .split_once('x')
+        .ok_or_else(|| format!("invalid dimensions '{}', expected '<width>x<height>'", s))?;
+    Ok((parse_dim(w)?, parse_dim(h)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_scale() {
+        assert_eq!(ResizeOp::parse("scale:800x600"), Ok(ResizeOp::Scale(800, 600)));
+    }
+
+    #[test]
+    fn parses_fit_width_and_fit_height() {
+        assert_eq!(ResizeOp::parse("fit-width:800"), Ok(ResizeOp::FitWidth(800)));
+        assert_eq!(ResizeOp::parse("fit-height:600"), Ok(ResizeOp::FitHeight(600)));
+    }
+
+    #[test]
+    fn parses_fit_and_pad() {
+        assert_eq!(ResizeOp::parse("fit:800x600"), Ok(ResizeOp::Fit(800, 600)));
+        assert_eq!(ResizeOp::parse("pad:200"), Ok(ResizeOp::Pad(200)));
+    }
+
+    #[test]
+    fn rejects_missing_colon() {
+        assert!(ResizeOp::parse("pad200").is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_kind() {
+        assert!(ResizeOp::parse("crop:200").is_err());
+    }
+
+    #[test]
+    fn rejects_non_numeric_dims() {
+        assert!(ResizeOp::parse("scale:800xabc").is_err());
+        assert!(ResizeOp::parse("fit-width:abc").is_err());
+    }
+
+    fn img(width: u32, height: u32) -> DynamicImage {
+        DynamicImage::new_rgba8(width, height)
+    }
+
+    #[test]
+    fn scale_ignores_aspect_ratio() {
+        let out = ResizeOp::Scale(50, 20).apply(&img(400, 200));
+        assert_eq!((out.width(), out.height()), (50, 20));
+    }
+
+    #[test]
+    fn fit_width_preserves_aspect_ratio() {
+        let out = ResizeOp::FitWidth(200).apply(&img(400, 200));
+        assert_eq!((out.width(), out.height()), (200, 100));
+    }
+
+    #[test]
+    fn fit_height_preserves_aspect_ratio() {
+        let out = ResizeOp::FitHeight(100).apply(&img(400, 200));
+        assert_eq!((out.width(), out.height()), (200, 100));
+    }
+
+    #[test]
+    fn fit_scales_down_to_the_tighter_dimension() {
+        // 400x200 into a 100x100 box: width is the limiting dimension (scale 0.25)
+        let out = ResizeOp::Fit(100, 100).apply(&img(400, 200));
+        assert_eq!((out.width(), out.height()), (100, 50));
+    }
+
+    #[test]
+    fn fit_never_upscales() {
+        let out = ResizeOp::Fit(800, 800).apply(&img(400, 200));
+        assert_eq!((out.width(), out.height()), (400, 200));
+    }
+
+    #[test]
+    fn pad_produces_a_square() {
+        let out = ResizeOp::Pad(64).apply(&img(400, 200));
+        assert_eq!((out.width(), out.height()), (64, 64));
+    }
+
+    #[test]
+    fn scaled_dim_preserves_ratio() {
+        assert_eq!(scaled_dim(200, 400, 200), 100);
+        assert_eq!(scaled_dim(1, 3, 1), 1);
+    }
+}
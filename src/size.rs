@@ -0,0 +1,64 @@
+/// A resize target expressed either as a named preset or a raw pixel count.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SizeOption {
+    Small,
+    Medium,
+    Large,
+    Custom(u32),
+}
+
+impl SizeOption {
+    /// Parses `small`, `medium`, `large`, or a raw pixel count like `200`.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "small" => Ok(SizeOption::Small),
+            "medium" => Ok(SizeOption::Medium),
+            "large" => Ok(SizeOption::Large),
+            other => other
+                .parse::<u32>()
+                .map(SizeOption::Custom)
+                .map_err(|_| format!("invalid size '{}', expected small, medium, large or a number", other)),
+        }
+    }
+
+    /// The pixel count this option resolves to.
+    pub fn pixels(self) -> u32 {
+        match self {
+            SizeOption::Small => 200,
+            SizeOption::Medium => 640,
+            SizeOption::Large => 1280,
+            SizeOption::Custom(pixels) => pixels,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_named_presets() {
+        assert_eq!(SizeOption::parse("small"), Ok(SizeOption::Small));
+        assert_eq!(SizeOption::parse("medium"), Ok(SizeOption::Medium));
+        assert_eq!(SizeOption::parse("large"), Ok(SizeOption::Large));
+    }
+
+    #[test]
+    fn parses_a_raw_pixel_count() {
+        assert_eq!(SizeOption::parse("350"), Ok(SizeOption::Custom(350)));
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert!(SizeOption::parse("huge").is_err());
+        assert!(SizeOption::parse("-1").is_err());
+    }
+
+    #[test]
+    fn resolves_to_expected_pixel_counts() {
+        assert_eq!(SizeOption::Small.pixels(), 200);
+        assert_eq!(SizeOption::Medium.pixels(), 640);
+        assert_eq!(SizeOption::Large.pixels(), 1280);
+        assert_eq!(SizeOption::Custom(42).pixels(), 42);
+    }
+}
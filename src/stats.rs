@@ -0,0 +1,185 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::Path,
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+};
+
+use image::ImageResult;
+
+use crate::StatsOpts;
+
+/// Tracks bytes saved across a (possibly parallel) tinify run.
+#[derive(Default)]
+pub struct Savings {
+    count: AtomicUsize,
+    input_bytes: AtomicU64,
+    output_bytes: AtomicU64,
+}
+
+impl Savings {
+    pub fn record(&self, input_len: u64, output_len: u64) {
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.input_bytes.fetch_add(input_len, Ordering::Relaxed);
+        self.output_bytes.fetch_add(output_len, Ordering::Relaxed);
+    }
+
+    // Print bytes saved and the percentage reduction
+    pub fn print_summary(&self) {
+        let count = self.count.load(Ordering::Relaxed);
+        if count == 0 {
+            return;
+        }
+        let input_bytes = self.input_bytes.load(Ordering::Relaxed);
+        let output_bytes = self.output_bytes.load(Ordering::Relaxed);
+        let saved = input_bytes.saturating_sub(output_bytes);
+        let percent = if input_bytes > 0 {
+            saved as f64 / input_bytes as f64 * 100.0
+        } else {
+            0.0
+        };
+
+        println!("--- Tinify Summary ---");
+        println!("Images processed: {}", count);
+        println!("Bytes before:      {}", input_bytes);
+        println!("Bytes after:       {}", output_bytes);
+        println!("Bytes saved:       {} ({:.1}%)", saved, percent);
+    }
+}
+
+/// Per-format counters for the `stats` subcommand's folder walk.
+#[derive(Default)]
+struct FormatStat {
+    count: u64,
+    total_bytes: u64,
+}
+
+/// Folder stats for the `stats` subcommand: counts and sizes grouped by extension.
+pub fn run(opts: &StatsOpts) -> ImageResult<()> {
+    let mut by_format: HashMap<String, FormatStat> = HashMap::new();
+    let mut total_count = 0u64;
+    let mut total_bytes = 0u64;
+
+    walk(Path::new(&opts.input_folder), opts.recursive, &mut |path, len| {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("(none)")
+            .to_lowercase();
+        let stat = by_format.entry(ext).or_default();
+        stat.count += 1;
+        stat.total_bytes += len;
+        total_count += 1;
+        total_bytes += len;
+    })?;
+
+    println!("--- Folder Stats: {} ---", opts.input_folder);
+    let mut formats: Vec<_> = by_format.into_iter().collect();
+    formats.sort_by(|a, b| a.0.cmp(&b.0));
+    for (ext, stat) in formats {
+        println!(
+            "{:<8} {:>6} files  {:>12} bytes total  {:>10} bytes mean",
+            ext,
+            stat.count,
+            stat.total_bytes,
+            stat.total_bytes / stat.count.max(1)
+        );
+    }
+    println!(
+        "{:<8} {:>6} files  {:>12} bytes total  {:>10} bytes mean",
+        "total",
+        total_count,
+        total_bytes,
+        total_bytes / total_count.max(1)
+    );
+
+    Ok(())
+}
+
+fn walk(dir: &Path, recursive: bool, visit: &mut dyn FnMut(&Path, u64)) -> ImageResult<()> {
+    if !dir.exists() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_dir() {
+            if recursive {
+                walk(&path, recursive, visit)?;
+            }
+            continue;
+        }
+        visit(&path, entry.metadata()?.len());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, sync::atomic::Ordering};
+
+    use super::*;
+
+    #[test]
+    fn record_accumulates_across_multiple_calls() {
+        let savings = Savings::default();
+        savings.record(100, 40);
+        savings.record(50, 20);
+
+        assert_eq!(savings.count.load(Ordering::Relaxed), 2);
+        assert_eq!(savings.input_bytes.load(Ordering::Relaxed), 150);
+        assert_eq!(savings.output_bytes.load(Ordering::Relaxed), 60);
+    }
+
+    #[test]
+    fn print_summary_does_not_panic_with_no_recordings() {
+        Savings::default().print_summary();
+    }
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("tinify-pics-stats-test-{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn walk_visits_top_level_files_only_when_not_recursive() {
+        let dir = scratch_dir("non-recursive");
+        fs::write(dir.join("a.png"), b"12345").unwrap();
+        fs::create_dir(dir.join("sub")).unwrap();
+        fs::write(dir.join("sub").join("b.png"), b"123").unwrap();
+
+        let mut visited = Vec::new();
+        walk(&dir, false, &mut |path, len| visited.push((path.to_path_buf(), len))).unwrap();
+
+        assert_eq!(visited, vec![(dir.join("a.png"), 5)]);
+    }
+
+    #[test]
+    fn walk_recurses_into_subfolders_when_recursive() {
+        let dir = scratch_dir("recursive");
+        fs::write(dir.join("a.png"), b"12345").unwrap();
+        fs::create_dir(dir.join("sub")).unwrap();
+        fs::write(dir.join("sub").join("b.png"), b"123").unwrap();
+
+        let mut total_bytes = 0u64;
+        let mut count = 0u64;
+        walk(&dir, true, &mut |_, len| {
+            total_bytes += len;
+            count += 1;
+        })
+        .unwrap();
+
+        assert_eq!(count, 2);
+        assert_eq!(total_bytes, 8);
+    }
+
+    #[test]
+    fn walk_on_a_missing_folder_is_a_no_op() {
+        let dir = std::env::temp_dir().join("tinify-pics-stats-test-does-not-exist");
+        let mut visited = 0u64;
+        walk(&dir, true, &mut |_, _| visited += 1).unwrap();
+        assert_eq!(visited, 0);
+    }
+}